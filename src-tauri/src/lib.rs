@@ -1,30 +1,299 @@
-use tauri::menu::{Menu, MenuBuilder, MenuEvent};
-use tauri::tray::TrayIconBuilder;
-use tauri::Manager;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuBuilder, MenuEvent, SubmenuBuilder};
+use tauri::tray::{TrayIconBuilder, TrayIconEvent};
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_updater::UpdaterExt;
 
 const TRAY_ID: &str = "main";
 
-fn build_tray_menu<R: tauri::Runtime>(app: &tauri::App<R>) -> tauri::Result<Menu<R>> {
-  let handle = app.handle();
-  MenuBuilder::new(handle)
+const PULSE_INTERVAL: Duration = Duration::from_millis(500);
+
+const UPDATE_STATUS_DISPLAY_DURATION: Duration = Duration::from_secs(4);
+
+static IDLE_ICON: &[u8] = include_bytes!("../icons/tray/idle.png");
+static PAUSED_ICON: &[u8] = include_bytes!("../icons/tray/paused.png");
+static PLAYING_FRAMES: &[&[u8]] = &[
+  include_bytes!("../icons/tray/playing-1.png"),
+  include_bytes!("../icons/tray/playing-2.png"),
+  include_bytes!("../icons/tray/playing-3.png"),
+];
+
+struct TrayIcons {
+  idle: Image<'static>,
+  paused: Image<'static>,
+  playing_frames: Vec<Image<'static>>,
+}
+
+impl TrayIcons {
+  fn load() -> tauri::Result<Self> {
+    Ok(Self {
+      idle: Image::from_bytes(IDLE_ICON)?,
+      paused: Image::from_bytes(PAUSED_ICON)?,
+      playing_frames: PLAYING_FRAMES
+        .iter()
+        .map(|bytes| Image::from_bytes(bytes))
+        .collect::<tauri::Result<_>>()?,
+    })
+  }
+}
+
+/// Generation counter for the "playing" pulse animation. `set_tray_playback`
+/// bumps this before (re)starting an animation thread so any previous thread
+/// notices the mismatch on its next tick and exits instead of racing the new
+/// one for control of the tray icon.
+#[derive(Default)]
+struct TrayAnimation {
+  generation: AtomicU64,
+}
+
+struct TrayClickTogglesWindow(AtomicBool);
+
+/// A platform double-click delivers a `Click`/`Up` event followed by a
+/// separate `DoubleClick` event. Gating the single-click action behind this
+/// delay, and bumping `TrayClickState.generation` on every click, lets a
+/// double-click cancel the pending single-click instead of firing both.
+const DOUBLE_CLICK_WINDOW: Duration = Duration::from_millis(350);
+
+#[derive(Default)]
+struct TrayClickState {
+  generation: AtomicU64,
+}
+
+struct CloseToTray(AtomicBool);
+
+#[derive(Default)]
+struct CloseToTrayNotified(AtomicBool);
+
+struct UpdateCheckInterval(AtomicU64);
+
+impl Default for UpdateCheckInterval {
+  fn default() -> Self {
+    Self(AtomicU64::new(6 * 60 * 60))
+  }
+}
+
+/// Guards against two update checks (e.g. a manual tray click and the
+/// periodic background check) running concurrently and racing to download
+/// and install the same update.
+#[derive(Default)]
+struct UpdateCheckInProgress(AtomicBool);
+
+#[derive(Default)]
+struct LastTrayStatus(Mutex<String>);
+
+const PRESETS: &[&str] = &["Focus", "Relax", "Deep Sleep", "Meditation"];
+
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlaybackStatus {
+  Playing,
+  Paused,
+  Idle,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct PlaybackState {
+  pub status: PlaybackStatus,
+  pub preset: String,
+}
+
+fn build_tray_menu<R: tauri::Runtime>(
+  app: &tauri::AppHandle<R>,
+  state: &PlaybackState,
+) -> tauri::Result<Menu<R>> {
+  let preset_items = PRESETS
+    .iter()
+    .map(|preset| {
+      CheckMenuItem::with_id(
+        app,
+        format!("preset:{preset}"),
+        *preset,
+        true,
+        *preset == state.preset,
+        None::<&str>,
+      )
+    })
+    .collect::<tauri::Result<Vec<_>>>()?;
+  let preset_item_refs = preset_items
+    .iter()
+    .map(|item| item as &dyn IsMenuItem<R>)
+    .collect::<Vec<_>>();
+  let preset_submenu = SubmenuBuilder::new(app, "Preset")
+    .items(&preset_item_refs)
+    .build()?;
+
+  MenuBuilder::new(app)
     .text("show", "Show")
+    .separator()
+    .text(
+      "toggle",
+      if state.status == PlaybackStatus::Playing {
+        "Pause"
+      } else {
+        "Play"
+      },
+    )
+    .text("next", "Next Preset")
+    .text("prev", "Previous Preset")
+    .item(&preset_submenu)
+    .separator()
+    .text("check_updates", "Check for Updates…")
     .text("quit", "Quit")
     .build()
 }
 
+#[tauri::command]
+fn rebuild_tray_menu(app: tauri::AppHandle<tauri::Wry>, state: PlaybackState) -> Result<(), String> {
+  let tray = app
+    .tray_by_id(TRAY_ID)
+    .ok_or_else(|| "Tray not found".to_string())?;
+  let menu = build_tray_menu(&app, &state).map_err(|e| e.to_string())?;
+  tray.set_menu(Some(menu)).map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+  ToggleWindow,
+  TogglePlayback,
+  NextPreset,
+  PrevPreset,
+}
+
+#[derive(Default)]
+struct GlobalShortcutBindings(Mutex<HashMap<Action, String>>);
+
+fn action_event_name(action: Action) -> Option<&'static str> {
+  match action {
+    Action::ToggleWindow => None,
+    Action::TogglePlayback => Some("tray://toggle"),
+    Action::NextPreset => Some("tray://next"),
+    Action::PrevPreset => Some("tray://prev"),
+  }
+}
+
+fn dispatch_action<R: tauri::Runtime>(app: &tauri::AppHandle<R>, action: Action) {
+  match action_event_name(action) {
+    Some(event) => {
+      let _ = app.emit(event, ());
+    }
+    None => toggle_main_window(app),
+  }
+}
+
+fn show_main_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+  if let Some(w) = app.get_webview_window("main") {
+    let _ = w.show();
+    let _ = w.set_focus();
+  }
+}
+
+fn toggle_main_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+  if let Some(w) = app.get_webview_window("main") {
+    let visible = w.is_visible().unwrap_or(false);
+    if visible {
+      let _ = w.hide();
+    } else {
+      let _ = w.show();
+      let _ = w.set_focus();
+    }
+  }
+}
+
+fn on_tray_icon_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: TrayIconEvent) {
+  match event {
+    TrayIconEvent::Click {
+      button: tauri::tray::MouseButton::Left,
+      button_state: tauri::tray::MouseButtonState::Up,
+      ..
+    } => {
+      let generation = app
+        .state::<TrayClickState>()
+        .generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+      let app_handle = app.clone();
+      std::thread::spawn(move || {
+        std::thread::sleep(DOUBLE_CLICK_WINDOW);
+        let current = app_handle.state::<TrayClickState>().generation.load(Ordering::SeqCst);
+        if current == generation {
+          handle_tray_click(&app_handle);
+        }
+      });
+    }
+    TrayIconEvent::DoubleClick {
+      button: tauri::tray::MouseButton::Left,
+      ..
+    } => {
+      app.state::<TrayClickState>().generation.fetch_add(1, Ordering::SeqCst);
+      handle_tray_click(app);
+    }
+    _ => {}
+  }
+}
+
+fn handle_tray_click<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+  let toggles = app
+    .state::<TrayClickTogglesWindow>()
+    .0
+    .load(Ordering::Relaxed);
+  if toggles {
+    dispatch_action(app, Action::ToggleWindow);
+  } else {
+    show_main_window(app);
+  }
+}
+
 fn on_tray_menu_event<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: MenuEvent) {
-  match event.id.as_ref() {
+  let id = event.id.as_ref();
+  match id {
     "show" => {
-      if let Some(w) = app.get_webview_window("main") {
-        let _ = w.show();
-        let _ = w.set_focus();
-      }
+      show_main_window(app);
+    }
+    "toggle" => {
+      dispatch_action(app, Action::TogglePlayback);
+    }
+    "next" => {
+      dispatch_action(app, Action::NextPreset);
+    }
+    "prev" => {
+      dispatch_action(app, Action::PrevPreset);
+    }
+    "check_updates" => {
+      let app_handle = app.clone();
+      tauri::async_runtime::spawn(async move {
+        check_for_updates(app_handle).await;
+      });
     }
     "quit" => {
       app.exit(0);
     }
-    _ => {}
+    _ => {
+      if let Some(preset) = id.strip_prefix("preset:") {
+        let _ = app.emit("tray://preset", preset);
+      }
+    }
+  }
+}
+
+fn set_tray_status_str<R: tauri::Runtime>(app: &tauri::AppHandle<R>, status: &str) -> Result<(), String> {
+  let tray = app
+    .tray_by_id(TRAY_ID)
+    .ok_or_else(|| "Tray not found".to_string())?;
+  tray.set_tooltip(Some(status)).map_err(|e| e.to_string())?;
+  #[cfg(target_os = "macos")]
+  {
+    tray.set_title(Some(status)).map_err(|e| e.to_string())?;
   }
+  Ok(())
 }
 
 #[tauri::command]
@@ -32,20 +301,261 @@ fn set_tray_status(
   app: tauri::AppHandle<tauri::Wry>,
   status: String,
 ) -> Result<(), String> {
+  *app.state::<LastTrayStatus>().0.lock().unwrap() = status.clone();
+  set_tray_status_str(&app, status.as_str())
+}
+
+#[tauri::command]
+fn set_tray_playback(app: tauri::AppHandle<tauri::Wry>, state: PlaybackState) -> Result<(), String> {
   let tray = app
     .tray_by_id(TRAY_ID)
     .ok_or_else(|| "Tray not found".to_string())?;
-  tray.set_tooltip(Some(status.as_str())).map_err(|e| e.to_string())?;
+  let icons = app.state::<TrayIcons>();
+  let generation = app
+    .state::<TrayAnimation>()
+    .generation
+    .fetch_add(1, Ordering::SeqCst)
+    + 1;
+
+  match state.status {
+    PlaybackStatus::Idle => {
+      tray.set_icon(Some(icons.idle.clone())).map_err(|e| e.to_string())?;
+    }
+    PlaybackStatus::Paused => {
+      tray.set_icon(Some(icons.paused.clone())).map_err(|e| e.to_string())?;
+    }
+    PlaybackStatus::Playing => {
+      tray
+        .set_icon(Some(icons.playing_frames[0].clone()))
+        .map_err(|e| e.to_string())?;
+      spawn_pulse(app.clone(), generation);
+    }
+  }
+
   #[cfg(target_os = "macos")]
-  {
-    tray.set_title(Some(status.as_str())).map_err(|e| e.to_string())?;
+  tray.set_icon_as_template(true).map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+/// Cycles through the "playing" frames until `TrayAnimation.generation` no
+/// longer matches `generation`, mirroring the debounce pattern used for the
+/// tray icon click above.
+fn spawn_pulse(app: tauri::AppHandle<tauri::Wry>, generation: u64) {
+  std::thread::spawn(move || {
+    let frame_count = app.state::<TrayIcons>().playing_frames.len();
+    let mut index = 1usize;
+    loop {
+      std::thread::sleep(PULSE_INTERVAL);
+      if app.state::<TrayAnimation>().generation.load(Ordering::SeqCst) != generation {
+        break;
+      }
+      let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        break;
+      };
+      let frame = app.state::<TrayIcons>().playing_frames[index % frame_count].clone();
+      let _ = tray.set_icon(Some(frame));
+      #[cfg(target_os = "macos")]
+      let _ = tray.set_icon_as_template(true);
+      index += 1;
+    }
+  });
+}
+
+#[tauri::command]
+fn set_tray_click_toggles_window(app: tauri::AppHandle<tauri::Wry>, toggles: bool) {
+  app
+    .state::<TrayClickTogglesWindow>()
+    .0
+    .store(toggles, Ordering::Relaxed);
+}
+
+#[tauri::command]
+fn set_close_to_tray(app: tauri::AppHandle<tauri::Wry>, close_to_tray: bool) {
+  app.state::<CloseToTray>().0.store(close_to_tray, Ordering::Relaxed);
+}
+
+fn on_main_window_event(window: &tauri::WebviewWindow, event: &tauri::WindowEvent) {
+  let tauri::WindowEvent::CloseRequested { api, .. } = event else {
+    return;
+  };
+
+  let app = window.app_handle();
+  if !app.state::<CloseToTray>().0.load(Ordering::Relaxed) {
+    return;
+  }
+
+  api.prevent_close();
+  let _ = window.hide();
+
+  let already_notified = app
+    .state::<CloseToTrayNotified>()
+    .0
+    .swap(true, Ordering::Relaxed);
+  if !already_notified {
+    let _ = app
+      .notification()
+      .builder()
+      .title("EchoFlow")
+      .body("EchoFlow is still running in the tray.")
+      .show();
+  }
+}
+
+fn default_global_shortcuts() -> HashMap<Action, String> {
+  HashMap::from([
+    (Action::ToggleWindow, "CmdOrCtrl+Shift+E".to_string()),
+    (Action::TogglePlayback, "CmdOrCtrl+Shift+Space".to_string()),
+    (Action::NextPreset, "CmdOrCtrl+Shift+Right".to_string()),
+    (Action::PrevPreset, "CmdOrCtrl+Shift+Left".to_string()),
+  ])
+}
+
+fn global_shortcuts_file<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<std::path::PathBuf, String> {
+  app
+    .path()
+    .app_config_dir()
+    .map(|dir| dir.join("global_shortcuts.json"))
+    .map_err(|e| e.to_string())
+}
+
+fn load_persisted_global_shortcuts<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<HashMap<Action, String>> {
+  let path = global_shortcuts_file(app).ok()?;
+  let contents = std::fs::read_to_string(path).ok()?;
+  serde_json::from_str(&contents).ok()
+}
+
+fn persist_global_shortcuts<R: tauri::Runtime>(
+  app: &tauri::AppHandle<R>,
+  bindings: &HashMap<Action, String>,
+) -> Result<(), String> {
+  let path = global_shortcuts_file(app)?;
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+  }
+  let json = serde_json::to_string_pretty(bindings).map_err(|e| e.to_string())?;
+  std::fs::write(path, json).map_err(|e| e.to_string())
+}
+
+fn apply_global_shortcuts(
+  app: &tauri::AppHandle<tauri::Wry>,
+  bindings: &HashMap<Action, String>,
+) -> Result<(), String> {
+  let shortcuts = app.global_shortcut();
+  shortcuts.unregister_all().map_err(|e| e.to_string())?;
+
+  for (&action, accelerator) in bindings {
+    let app_handle = app.clone();
+    let result = shortcuts.on_shortcut(accelerator.as_str(), move |_app, _shortcut, event| {
+      if event.state == ShortcutState::Pressed {
+        dispatch_action(&app_handle, action);
+      }
+    });
+    if let Err(e) = result {
+      log::warn!("skipping global shortcut \"{accelerator}\" for {action:?}: {e}");
+    }
   }
+
   Ok(())
 }
 
+#[tauri::command]
+fn set_global_shortcuts(
+  app: tauri::AppHandle<tauri::Wry>,
+  bindings: HashMap<Action, String>,
+) -> Result<(), String> {
+  apply_global_shortcuts(&app, &bindings)?;
+  persist_global_shortcuts(&app, &bindings)?;
+  *app.state::<GlobalShortcutBindings>().0.lock().unwrap() = bindings;
+  Ok(())
+}
+
+async fn check_for_updates<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+  let in_progress = app.state::<UpdateCheckInProgress>();
+  if in_progress.0.swap(true, Ordering::SeqCst) {
+    return;
+  }
+
+  run_update_check(&app).await;
+
+  // Leave the terminal status ("Update available", "Up to date", ...) visible
+  // for a bit before reverting the tooltip, otherwise it's replaced in the
+  // same tick and the user never sees it.
+  tokio::time::sleep(UPDATE_STATUS_DISPLAY_DURATION).await;
+
+  let last_status = app.state::<LastTrayStatus>().0.lock().unwrap().clone();
+  let _ = set_tray_status_str(&app, &last_status);
+
+  app.state::<UpdateCheckInProgress>().0.store(false, Ordering::SeqCst);
+}
+
+async fn run_update_check<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+  let _ = set_tray_status_str(app, "Checking for updates…");
+
+  let updater = match app.updater() {
+    Ok(updater) => updater,
+    Err(e) => {
+      let _ = app.emit("updater://error", e.to_string());
+      let _ = set_tray_status_str(app, "Update check failed");
+      return;
+    }
+  };
+
+  match updater.check().await {
+    Ok(Some(update)) => {
+      let _ = app.emit("updater://available", update.version.clone());
+      let _ = set_tray_status_str(app, "Update available");
+
+      let mut downloaded = 0usize;
+      let progress_handle = app.clone();
+      let started_handle = app.clone();
+      let install_result = update
+        .download_and_install(
+          move |chunk_len, total_len| {
+            downloaded += chunk_len;
+            let _ = progress_handle.emit("updater://progress", (downloaded, total_len));
+          },
+          move || {
+            let _ = set_tray_status_str(&started_handle, "Downloading update…");
+          },
+        )
+        .await;
+
+      match install_result {
+        Ok(()) => {
+          let _ = app.emit("updater://installed", ());
+          let _ = set_tray_status_str(app, "Update installed");
+        }
+        Err(e) => {
+          let _ = app.emit("updater://error", e.to_string());
+          let _ = set_tray_status_str(app, "Update failed");
+        }
+      }
+    }
+    Ok(None) => {
+      let _ = app.emit("updater://up_to_date", ());
+      let _ = set_tray_status_str(app, "Up to date");
+    }
+    Err(e) => {
+      let _ = app.emit("updater://error", e.to_string());
+      let _ = set_tray_status_str(app, "Update check failed");
+    }
+  }
+}
+
+#[tauri::command]
+fn set_update_check_interval(app: tauri::AppHandle<tauri::Wry>, seconds: u64) {
+  app
+    .state::<UpdateCheckInterval>()
+    .0
+    .store(seconds, Ordering::Relaxed);
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
+    .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+    .plugin(tauri_plugin_updater::Builder::new().build())
     .setup(|app| {
       if cfg!(debug_assertions) {
         app.handle().plugin(
@@ -55,8 +565,42 @@ pub fn run() {
         )?;
       }
 
+      app.handle().plugin(tauri_plugin_notification::init())?;
+
+      app.manage(TrayClickTogglesWindow(AtomicBool::new(true)));
+      app.manage(TrayClickState::default());
+      app.manage(CloseToTray(AtomicBool::new(true)));
+      app.manage(CloseToTrayNotified::default());
+      app.manage(TrayIcons::load()?);
+      app.manage(TrayAnimation::default());
+      app.manage(GlobalShortcutBindings::default());
+      app.manage(UpdateCheckInterval::default());
+      app.manage(UpdateCheckInProgress::default());
+      app.manage(LastTrayStatus(Mutex::new("EchoFlow".to_string())));
+
+      let update_check_handle = app.handle().clone();
+      tauri::async_runtime::spawn(async move {
+        loop {
+          check_for_updates(update_check_handle.clone()).await;
+          let seconds = update_check_handle
+            .state::<UpdateCheckInterval>()
+            .0
+            .load(Ordering::Relaxed);
+          tokio::time::sleep(Duration::from_secs(seconds)).await;
+        }
+      });
+
+      let shortcuts =
+        load_persisted_global_shortcuts(app.handle()).unwrap_or_else(default_global_shortcuts);
+      apply_global_shortcuts(app.handle(), &shortcuts)?;
+      *app.state::<GlobalShortcutBindings>().0.lock().unwrap() = shortcuts;
+
       let handle = app.handle();
-      let menu = build_tray_menu(app)?;
+      let initial_state = PlaybackState {
+        status: PlaybackStatus::Idle,
+        preset: PRESETS[0].to_string(),
+      };
+      let menu = build_tray_menu(handle, &initial_state)?;
       let icon = handle
         .default_window_icon()
         .cloned()
@@ -68,11 +612,78 @@ pub fn run() {
         .tooltip("EchoFlow")
         .icon_as_template(true)
         .on_menu_event(on_tray_menu_event)
+        .on_tray_icon_event(on_tray_icon_event)
         .build(handle)?;
 
+      if let Some(window) = app.get_webview_window("main") {
+        let window_for_event = window.clone();
+        window.on_window_event(move |event| on_main_window_event(&window_for_event, event));
+      }
+
       Ok(())
     })
-    .invoke_handler(tauri::generate_handler![set_tray_status])
+    .invoke_handler(tauri::generate_handler![
+      set_tray_status,
+      set_tray_playback,
+      rebuild_tray_menu,
+      set_tray_click_toggles_window,
+      set_close_to_tray,
+      set_global_shortcuts,
+      set_update_check_interval
+    ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn action_event_name_maps_each_action() {
+    assert_eq!(action_event_name(Action::ToggleWindow), None);
+    assert_eq!(action_event_name(Action::TogglePlayback), Some("tray://toggle"));
+    assert_eq!(action_event_name(Action::NextPreset), Some("tray://next"));
+    assert_eq!(action_event_name(Action::PrevPreset), Some("tray://prev"));
+  }
+
+  #[test]
+  fn action_serializes_as_snake_case() {
+    assert_eq!(serde_json::to_string(&Action::ToggleWindow).unwrap(), "\"toggle_window\"");
+    assert_eq!(serde_json::to_string(&Action::TogglePlayback).unwrap(), "\"toggle_playback\"");
+    assert_eq!(serde_json::to_string(&Action::NextPreset).unwrap(), "\"next_preset\"");
+    assert_eq!(serde_json::to_string(&Action::PrevPreset).unwrap(), "\"prev_preset\"");
+  }
+
+  #[test]
+  fn playback_status_serializes_as_lowercase() {
+    assert_eq!(serde_json::to_string(&PlaybackStatus::Playing).unwrap(), "\"playing\"");
+    assert_eq!(serde_json::to_string(&PlaybackStatus::Paused).unwrap(), "\"paused\"");
+    assert_eq!(serde_json::to_string(&PlaybackStatus::Idle).unwrap(), "\"idle\"");
+  }
+
+  #[test]
+  fn stale_generation_stops_pulse() {
+    let animation = TrayAnimation::default();
+    let generation = animation.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    // A second playback change bumps the generation again, so the first
+    // animation thread's `generation` no longer matches and it should stop.
+    animation.generation.fetch_add(1, Ordering::SeqCst);
+    assert_ne!(animation.generation.load(Ordering::SeqCst), generation);
+  }
+
+  #[test]
+  fn matching_generation_keeps_pulse_running() {
+    let animation = TrayAnimation::default();
+    let generation = animation.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    assert_eq!(animation.generation.load(Ordering::SeqCst), generation);
+  }
+
+  #[test]
+  fn double_click_invalidates_pending_single_click() {
+    let state = TrayClickState::default();
+    let single_click_generation = state.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    state.generation.fetch_add(1, Ordering::SeqCst);
+    assert_ne!(state.generation.load(Ordering::SeqCst), single_click_generation);
+  }
+}